@@ -3,11 +3,12 @@
 // Add these new imports for serialization/deserialization if not already there
 // (You should have added them in Cargo.toml already for previous errors)
 use serde::{Serialize, Deserialize}; 
-use serde_json; // To serialize/deserialize the blockchain to/from file
 
 use std::time::{SystemTime, UNIX_EPOCH};
 use sha2::{Sha256, Digest};
+use std::collections::HashMap; // For the transaction index
 use std::fs; // For file system operations
+use std::io; // For propagating file errors
 use std::path::Path; // For path manipulation
 
 
@@ -34,12 +35,17 @@ impl Transaction {
     }
 
     // A simple method to get a hash of the transaction for verification
-    pub fn calculate_hash(&self) -> String {
+    pub fn calculate_hash(&self) -> TxHash {
         let mut hasher = Sha256::new();
-        hasher.update(self.sender.as_bytes());
-        hasher.update(self.receiver.as_bytes());
-        hasher.update(self.amount.to_string().as_bytes());
-        hasher.update(self.timestamp.to_string().as_bytes());
+        // Length-prefix the strings and use fixed-width numbers so that field
+        // boundaries are unambiguous ("AB"+"C" must not hash like "A"+"BC")
+        for field in [&self.sender, &self.receiver] {
+            hasher.update((field.len() as u64).to_le_bytes());
+            hasher.update(field.as_bytes());
+        }
+        hasher.update(self.amount.to_le_bytes());
+        hasher.update(self.timestamp.to_le_bytes());
+        hasher.update((self.signature.len() as u64).to_le_bytes());
         hasher.update(self.signature.as_bytes());
         format!("{:x}", hasher.finalize())
     }
@@ -92,21 +98,78 @@ impl Block {
 }
 
 
+// --- New: Transaction hash as returned by Transaction::calculate_hash ---
+pub type TxHash = String;
+
+
+// --- New: Reasons a transaction can be refused by add_transaction ---
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionError {
+    ZeroAmount, // A zero-amount transfer moves nothing and only clutters the block
+    DuplicateTransaction { tx_hash: TxHash }, // Same hash as a transaction already pending or mined
+}
+
+impl std::fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransactionError::ZeroAmount => write!(f, "Transaction amount must be greater than zero"),
+            TransactionError::DuplicateTransaction { tx_hash } => {
+                write!(f, "Transaction {} is already pending or mined", tx_hash)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TransactionError {}
+
+
+// --- New: Block interval statistics ---
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockTimeStats {
+    pub avg: f64,    // Average seconds between blocks
+    pub median: f64, // Median seconds between blocks
+    pub min: u64,
+    pub max: u64,
+}
+
+
+// --- New: Result of looking up a transaction by hash ---
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionStatus {
+    Pending,
+    Confirmed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionLookup {
+    pub transaction: Transaction,
+    pub status: TransactionStatus,
+    pub block_index: Option<u64>,   // Set once the transaction is mined
+    pub block_hash: Option<String>, // Set once the transaction is mined
+    pub confirmations: u64,         // 0 while pending, 1 when in the latest block
+}
+
+
 // --- Modified Blockchain Struct ---
 #[derive(Debug, Serialize, Deserialize)] // Added Serialize/Deserialize
 pub struct Blockchain {
+    pub version: u32, // On-disk format version, see BLOCKCHAIN_FORMAT_VERSION
     pub chain: Vec<Block>,
     pub difficulty: usize,
-    #[serde(skip)] // Don't serialize pending_transactions, they are transient
+    // Saved alongside the chain so submitted transactions survive a restart
     pub pending_transactions: Vec<Transaction>, // New: To hold transactions waiting to be mined
+    #[serde(skip)] // Derived from the chain, rebuilt on load
+    pub tx_index: HashMap<TxHash, u64>, // New: Confirmed transaction hash -> block index
 }
 
 impl Blockchain {
     pub fn new() -> Self {
         let mut blockchain = Blockchain {
+            version: BLOCKCHAIN_FORMAT_VERSION,
             chain: Vec::new(),
             difficulty: 4,
             pending_transactions: Vec::new(), // Initialize
+            tx_index: HashMap::new(),
         };
         blockchain.chain.push(blockchain.create_genesis_block());
         blockchain
@@ -138,11 +201,43 @@ impl Blockchain {
         new_block
     }
 
-    // New: Add a transaction to the pending pool
-    pub fn add_transaction(&mut self, transaction: Transaction) {
+    // New: Add a transaction to the pending pool, returning its hash
+    pub fn add_transaction(&mut self, transaction: Transaction) -> Result<TxHash, TransactionError> {
         // Here you would typically add validation logic (e.g., check sender balance, signature)
+        if transaction.amount == 0 {
+            return Err(TransactionError::ZeroAmount);
+        }
+        let tx_hash = transaction.calculate_hash();
+        if self.tx_index.contains_key(&tx_hash)
+            || self.pending_transactions.iter().any(|t| t.calculate_hash() == tx_hash) {
+            return Err(TransactionError::DuplicateTransaction { tx_hash });
+        }
         println!("  Added pending transaction: {} from {} to {}", transaction.amount, transaction.sender, transaction.receiver);
         self.pending_transactions.push(transaction);
+        Ok(tx_hash)
+    }
+
+    // New: Cancel a pending transaction by hash before it gets mined.
+    // NOTE: there is no authentication yet. The caller only has to repeat the transaction's
+    // placeholder signature, which anyone who can read the pending pool can see.
+    pub fn cancel_transaction(&mut self, tx_hash: TxHash, signature: String) -> Result<(), String> {
+        let position = match self.pending_transactions.iter().position(|t| t.calculate_hash() == tx_hash) {
+            Some(position) => position,
+            None => {
+                if self.tx_index.contains_key(&tx_hash) {
+                    return Err(format!("Transaction {} is already mined and cannot be cancelled", tx_hash));
+                }
+                return Err(format!("Transaction {} not found in pending transactions", tx_hash));
+            }
+        };
+
+        if self.pending_transactions[position].signature != signature {
+            return Err(format!("Signature does not match transaction {}", tx_hash));
+        }
+
+        let transaction = self.pending_transactions.remove(position);
+        println!("  Cancelled pending transaction: {} from {} to {}", transaction.amount, transaction.sender, transaction.receiver);
+        Ok(())
     }
 
     // New: Function to add a mined block to the chain
@@ -162,9 +257,80 @@ impl Blockchain {
             return;
         }
 
+        for transaction in &block.transactions {
+            // Keep the first location if the same hash ever shows up again
+            self.tx_index.entry(transaction.calculate_hash()).or_insert(block.index);
+        }
         self.chain.push(block);
     }
 
+    // New: Rebuild the transaction index from the chain (it isn't persisted)
+    pub fn rebuild_tx_index(&mut self) {
+        self.tx_index.clear();
+        for block in &self.chain {
+            for transaction in &block.transactions {
+                self.tx_index.entry(transaction.calculate_hash()).or_insert(block.index);
+            }
+        }
+    }
+
+    // New: Find a transaction by hash, whether it is still pending or already mined
+    pub fn get_transaction(&self, tx_hash: &str) -> Option<TransactionLookup> {
+        if let Some(&block_index) = self.tx_index.get(tx_hash) {
+            let block = self.chain.get(block_index as usize)?;
+            let transaction = block.transactions.iter().find(|t| t.calculate_hash() == tx_hash)?;
+            return Some(TransactionLookup {
+                transaction: transaction.clone(),
+                status: TransactionStatus::Confirmed,
+                block_index: Some(block.index),
+                block_hash: Some(block.hash.clone()),
+                confirmations: self.get_latest_block().index - block.index + 1,
+            });
+        }
+
+        self.pending_transactions
+            .iter()
+            .find(|t| t.calculate_hash() == tx_hash)
+            .map(|transaction| TransactionLookup {
+                transaction: transaction.clone(),
+                status: TransactionStatus::Pending,
+                block_index: None,
+                block_hash: None,
+                confirmations: 0,
+            })
+    }
+
+    // New: Statistics over the last `window` block intervals (the gaps between the last
+    // `window + 1` blocks). A window larger than the chain just uses every available interval;
+    // a window of 0 or a chain with only the genesis block reports zeros.
+    pub fn block_time_stats(&self, window: usize) -> BlockTimeStats {
+        let start = self.chain.len().saturating_sub(window.saturating_add(1));
+        let mut intervals: Vec<u64> = self.chain[start..]
+            .windows(2)
+            .map(|pair| pair[1].timestamp.saturating_sub(pair[0].timestamp))
+            .collect();
+
+        if intervals.is_empty() {
+            return BlockTimeStats { avg: 0.0, median: 0.0, min: 0, max: 0 };
+        }
+
+        intervals.sort_unstable();
+        let count = intervals.len();
+        let avg = intervals.iter().sum::<u64>() as f64 / count as f64;
+        let median = if count.is_multiple_of(2) {
+            (intervals[count / 2 - 1] + intervals[count / 2]) as f64 / 2.0
+        } else {
+            intervals[count / 2] as f64
+        };
+
+        BlockTimeStats {
+            avg,
+            median,
+            min: intervals[0],
+            max: intervals[count - 1],
+        }
+    }
+
     pub fn is_chain_valid(&self) -> bool {
         for i in 1..self.chain.len() {
             let current_block = &self.chain[i];
@@ -193,29 +359,116 @@ impl Blockchain {
     }
 }
 
+impl Default for Blockchain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 
 // --- New: Persistence functions ---
 const BLOCKCHAIN_FILE: &str = "blockchain.json";
 
-pub fn load_blockchain_from_file() -> Blockchain {
-    if Path::new(BLOCKCHAIN_FILE).exists() {
-        let content = fs::read_to_string(BLOCKCHAIN_FILE).expect("Failed to read blockchain file");
-        serde_json::from_str(&content).expect("Failed to deserialize blockchain")
+// Bump this whenever the serialized Blockchain layout changes, and add a step to migrate_blockchain_data.
+// Files written before versioning existed have no "version" field and are treated as version 0.
+const BLOCKCHAIN_FORMAT_VERSION: u32 = 2;
+
+// Upgrades raw blockchain JSON from an older format version to the current layout, one version at a time.
+fn migrate_blockchain_data(mut data: serde_json::Value) -> Result<serde_json::Value, String> {
+    if !data.is_object() {
+        return Err("Blockchain file is not a JSON object".to_string());
+    }
+
+    let too_new = |version: u64| format!(
+        "Blockchain file version {} is newer than the supported version {}. Please upgrade the node.",
+        version, BLOCKCHAIN_FORMAT_VERSION
+    );
+
+    let mut version = match data.get("version") {
+        Some(v) => {
+            let raw = v.as_u64().ok_or("Blockchain file has a non-numeric version")?;
+            // Anything that doesn't fit in a u32 is certainly newer than this node understands
+            u32::try_from(raw).map_err(|_| too_new(raw))?
+        }
+        None => 0,
+    };
+
+    if version > BLOCKCHAIN_FORMAT_VERSION {
+        return Err(too_new(version as u64));
+    }
+
+    while version < BLOCKCHAIN_FORMAT_VERSION {
+        match version {
+            // v0 -> v1: same layout, only the version field is added
+            0 => {}
+            // v1 -> v2: pending transactions are saved, older files have none
+            1 => {
+                data["pending_transactions"] = serde_json::Value::Array(Vec::new());
+            }
+            _ => unreachable!("No migration defined from blockchain format version {}", version),
+        }
+        version += 1;
+        data["version"] = serde_json::Value::from(version);
+    }
+
+    Ok(data)
+}
+
+pub fn load_blockchain_from_file() -> Result<Blockchain, String> {
+    load_blockchain_from(Path::new(BLOCKCHAIN_FILE))
+}
+
+pub fn load_blockchain_from(path: &Path) -> Result<Blockchain, String> {
+    if path.exists() {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let data: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+        let data = migrate_blockchain_data(data)?;
+        let mut blockchain: Blockchain = serde_json::from_value(data)
+            .map_err(|e| format!("Failed to deserialize blockchain: {}", e))?;
+        blockchain.rebuild_tx_index();
+        // Drop anything that was mined after the pending pool was saved
+        let tx_index = &blockchain.tx_index;
+        blockchain.pending_transactions.retain(|t| !tx_index.contains_key(&t.calculate_hash()));
+        if !blockchain.pending_transactions.is_empty() {
+            println!("Restored {} pending transactions.", blockchain.pending_transactions.len());
+        }
+        Ok(blockchain)
     } else {
         println!("No existing blockchain file found. Creating new blockchain.");
-        Blockchain::new()
+        Ok(Blockchain::new())
     }
 }
 
-pub fn save_blockchain_to_file(blockchain: &Blockchain) {
-    let data = serde_json::to_string_pretty(blockchain).expect("Failed to serialize blockchain");
-    fs::write(BLOCKCHAIN_FILE, data).expect("Failed to write blockchain file");
-    println!("Blockchain saved to {}.", BLOCKCHAIN_FILE);
+// Returns the error instead of panicking so a transient I/O failure doesn't take the node down
+pub fn save_blockchain_to_file(blockchain: &Blockchain) -> Result<(), io::Error> {
+    save_blockchain_to(Path::new(BLOCKCHAIN_FILE), blockchain)
 }
 
+pub fn save_blockchain_to(path: &Path, blockchain: &Blockchain) -> Result<(), io::Error> {
+    let data = serde_json::to_string_pretty(blockchain)?;
+    fs::write(path, data)?;
+    println!("Blockchain saved to {}.", path.display());
+    Ok(())
+}
+
+
+// Demo helper: add a transaction to the pool and report why it was refused, if it was
+fn submit_transaction(blockchain: &mut Blockchain, transaction: Transaction) {
+    if let Err(e) = blockchain.add_transaction(transaction) {
+        println!("  Transaction rejected: {}", e);
+    }
+}
 
 fn main() {
-    let mut my_blockchain = load_blockchain_from_file(); 
+    let mut my_blockchain = match load_blockchain_from_file() {
+        Ok(blockchain) => blockchain,
+        Err(e) => {
+            println!("Error: Failed to load blockchain: {}", e);
+            std::process::exit(1);
+        }
+    };
 
     println!("Blockchain loaded. Current latest block index: {}", my_blockchain.get_latest_block().index);
     println!("Is blockchain valid: {}", my_blockchain.is_chain_valid());
@@ -224,10 +477,10 @@ fn main() {
     println!("\n--- Test 1: Simulating transactions and mining ---");
     
     // Add some pending transactions
-    my_blockchain.add_transaction(Transaction::new(
+    submit_transaction(&mut my_blockchain, Transaction::new(
         "AddressA".to_string(), "AddressB".to_string(), 10, "sigA1".to_string()
     ));
-    my_blockchain.add_transaction(Transaction::new(
+    submit_transaction(&mut my_blockchain, Transaction::new(
         "AddressC".to_string(), "AddressD".to_string(), 5, "sigC1".to_string()
     ));
 
@@ -247,12 +500,23 @@ fn main() {
 
     // --- Test 2: Add more transactions and mine another block ---
     println!("\n--- Test 2: Adding more transactions and mining again ---");
-    my_blockchain.add_transaction(Transaction::new(
+    submit_transaction(&mut my_blockchain, Transaction::new(
         "AddressB".to_string(), "AddressE".to_string(), 3, "sigB1".to_string()
     ));
-    my_blockchain.add_transaction(Transaction::new(
+    submit_transaction(&mut my_blockchain, Transaction::new(
         "AddressF".to_string(), "AddressA".to_string(), 20, "sigF1".to_string()
     ));
+
+    // A mistaken transaction can still be cancelled while it is pending
+    let mistaken_tx_hash = my_blockchain.add_transaction(Transaction::new(
+        "AddressB".to_string(), "AddressZ".to_string(), 300, "sigB2".to_string()
+    )).expect("Mistaken transaction should be accepted");
+    if let Err(e) = my_blockchain.cancel_transaction(mistaken_tx_hash.clone(), "sigWrong".to_string()) {
+        println!("  Cancellation with the wrong signature rejected: {}", e);
+    }
+    if let Err(e) = my_blockchain.cancel_transaction(mistaken_tx_hash, "sigB2".to_string()) {
+        println!("  Cancellation failed: {}", e);
+    }
     
     println!("Mining another block with {} pending transactions...", my_blockchain.pending_transactions.len());
     let mined_block_2 = my_blockchain.mine_block();
@@ -268,6 +532,15 @@ fn main() {
         return;
     }
 
+    // Look up a mined transaction by its hash
+    if let Some(first_tx) = mined_block_2.transactions.first() {
+        match my_blockchain.get_transaction(&first_tx.calculate_hash()) {
+            Some(lookup) => println!("  Found transaction in block {:?} with {} confirmation(s), status {:?}",
+                                     lookup.block_index, lookup.confirmations, lookup.status),
+            None => println!("  Transaction {} not found!", first_tx.calculate_hash()),
+        }
+    }
+
 
     // --- Test 3: Large scale block generation (similar to your 15M -> 17M goal) ---
     // Note: If difficulty is high, this will take a very long time!
@@ -277,10 +550,10 @@ fn main() {
 
     for i in start_gen_index..=end_gen_index {
         // Simulate adding some transactions for each block
-        my_blockchain.add_transaction(Transaction::new(
+        submit_transaction(&mut my_blockchain, Transaction::new(
             format!("Sender{}", i), format!("Receiver{}", i), i % 100 + 1, format!("sig{}", i)
         ));
-        my_blockchain.add_transaction(Transaction::new(
+        submit_transaction(&mut my_blockchain, Transaction::new(
             format!("SenderX{}", i), format!("ReceiverY{}", i), (i % 50) * 2, format!("sigX{}", i)
         ));
 
@@ -307,7 +580,381 @@ fn main() {
     println!("Total blocks in chain: {}", my_blockchain.chain.len());
     println!("Latest block index: {}", my_blockchain.get_latest_block().index);
     println!("Is blockchain valid: {}", my_blockchain.is_chain_valid());
+    let block_times = my_blockchain.block_time_stats(100);
+    println!("Block time over last 100 block intervals: avg {:.2}s, median {:.1}s, min {}s, max {}s",
+             block_times.avg, block_times.median, block_times.min, block_times.max);
 
     // Save the final state of the blockchain
-    save_blockchain_to_file(&my_blockchain);
+    if let Err(e) = save_blockchain_to_file(&my_blockchain) {
+        println!("Error: Failed to save blockchain to {}: {}", BLOCKCHAIN_FILE, e);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    // Genesis block of the blockchain.json shipped before format versioning existed (v0: no "version")
+    const V0_FIXTURE: &str = r#"{
+        "chain": [
+            {
+                "index": 0,
+                "previous_hash": "0",
+                "timestamp": 1760266228,
+                "transactions": [],
+                "nonce": 0,
+                "hash": "bacc14da823d4bbe1176ba74a384fe8448dfceabf7df82c2c8343a3fb73376eb"
+            }
+        ],
+        "difficulty": 4
+    }"#;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("novachain-{}-{}.json", name, std::process::id()))
+    }
+
+    fn load_from_contents(name: &str, contents: &str) -> Result<Blockchain, String> {
+        let path = temp_path(name);
+        fs::write(&path, contents).unwrap();
+        let result = load_blockchain_from(&path);
+        fs::remove_file(&path).unwrap();
+        result
+    }
+
+    #[test]
+    fn migrate_v0_data_without_version() {
+        let migrated = migrate_blockchain_data(json!({ "chain": [], "difficulty": 4 })).unwrap();
+        assert_eq!(migrated["version"], json!(BLOCKCHAIN_FORMAT_VERSION));
+    }
+
+    #[test]
+    fn migrate_v1_adds_empty_pending_list() {
+        let migrated = migrate_blockchain_data(json!({ "version": 1, "chain": [], "difficulty": 4 })).unwrap();
+        assert_eq!(migrated["version"], json!(BLOCKCHAIN_FORMAT_VERSION));
+        assert_eq!(migrated["pending_transactions"], json!([]));
+    }
+
+    #[test]
+    fn migrate_rejects_non_object() {
+        let err = migrate_blockchain_data(json!([])).unwrap_err();
+        assert_eq!(err, "Blockchain file is not a JSON object");
+    }
+
+    #[test]
+    fn migrate_rejects_version_beyond_u32() {
+        // 2^32 + 1 would read as version 1 if truncated to u32
+        let err = migrate_blockchain_data(json!({ "version": 4294967297u64, "chain": [], "difficulty": 4 })).unwrap_err();
+        assert!(err.contains("4294967297"), "{}", err);
+        assert!(err.contains("newer than the supported version"), "{}", err);
+    }
+
+    #[test]
+    fn load_v0_file() {
+        let blockchain = load_from_contents("v0", V0_FIXTURE).unwrap();
+        assert_eq!(blockchain.version, BLOCKCHAIN_FORMAT_VERSION);
+        assert_eq!(blockchain.chain.len(), 1);
+        assert_eq!(blockchain.chain[0].hash, blockchain.chain[0].calculate_hash());
+        assert!(blockchain.is_chain_valid());
+    }
+
+    #[test]
+    fn load_current_version_file() {
+        let original = Blockchain::new();
+        let contents = serde_json::to_string(&original).unwrap();
+        let blockchain = load_from_contents("current", &contents).unwrap();
+        assert_eq!(blockchain.version, BLOCKCHAIN_FORMAT_VERSION);
+        assert_eq!(blockchain.chain[0].hash, original.chain[0].hash);
+    }
+
+    #[test]
+    fn load_future_version_file_is_refused() {
+        let mut data = serde_json::to_value(Blockchain::new()).unwrap();
+        data["version"] = json!(BLOCKCHAIN_FORMAT_VERSION + 1);
+        let err = load_from_contents("future", &data.to_string()).unwrap_err();
+        assert!(err.contains("newer than the supported version"), "{}", err);
+    }
+
+    fn test_blockchain() -> Blockchain {
+        let mut blockchain = Blockchain::new();
+        blockchain.difficulty = 1; // Keep proof-of-work cheap in tests
+        blockchain
+    }
+
+    fn test_transaction(sender: &str, amount: u64, signature: &str) -> Transaction {
+        Transaction::new(sender.to_string(), "Receiver".to_string(), amount, signature.to_string())
+    }
+
+    fn mine(blockchain: &mut Blockchain) {
+        let block = blockchain.mine_block();
+        blockchain.add_mined_block(block);
+    }
+
+    #[test]
+    fn cancel_pending_transaction() {
+        let mut blockchain = test_blockchain();
+        let tx = test_transaction("AddressA", 10, "sigA1");
+        let tx_hash = tx.calculate_hash();
+        blockchain.add_transaction(tx).unwrap();
+        blockchain.add_transaction(test_transaction("AddressC", 5, "sigC1")).unwrap();
+
+        blockchain.cancel_transaction(tx_hash, "sigA1".to_string()).unwrap();
+        assert_eq!(blockchain.pending_transactions.len(), 1);
+        assert_eq!(blockchain.pending_transactions[0].sender, "AddressC");
+    }
+
+    #[test]
+    fn cancel_only_removes_addressed_transaction_when_signatures_match() {
+        let mut blockchain = test_blockchain();
+        let first = Transaction { sender: "AB".to_string(), receiver: "C".to_string(), amount: 10,
+                                  timestamp: 1_700_000_000, signature: "shared".to_string() };
+        let second = Transaction { sender: "A".to_string(), receiver: "BC".to_string(), amount: 10,
+                                   timestamp: 1_700_000_000, signature: "shared".to_string() };
+        let first_hash = blockchain.add_transaction(first).unwrap();
+        blockchain.add_transaction(second).unwrap();
+
+        blockchain.cancel_transaction(first_hash, "shared".to_string()).unwrap();
+        assert_eq!(blockchain.pending_transactions.len(), 1);
+        assert_eq!(blockchain.pending_transactions[0].sender, "A");
+        assert_eq!(blockchain.pending_transactions[0].receiver, "BC");
+    }
+
+    #[test]
+    fn cancel_already_mined_transaction_is_rejected() {
+        let mut blockchain = test_blockchain();
+        let tx = test_transaction("AddressA", 10, "sigA1");
+        let tx_hash = tx.calculate_hash();
+        blockchain.add_transaction(tx).unwrap();
+        mine(&mut blockchain);
+
+        let err = blockchain.cancel_transaction(tx_hash, "sigA1".to_string()).unwrap_err();
+        assert!(err.contains("already mined"), "{}", err);
+        assert_eq!(blockchain.chain[1].transactions.len(), 1);
+    }
+
+    #[test]
+    fn cancel_with_wrong_signature_is_rejected() {
+        let mut blockchain = test_blockchain();
+        let tx = test_transaction("AddressA", 10, "sigA1");
+        let tx_hash = tx.calculate_hash();
+        blockchain.add_transaction(tx).unwrap();
+
+        let err = blockchain.cancel_transaction(tx_hash, "sigOther".to_string()).unwrap_err();
+        assert!(err.contains("Signature does not match"), "{}", err);
+        assert_eq!(blockchain.pending_transactions.len(), 1);
+    }
+
+    #[test]
+    fn zero_amount_transaction_is_not_pooled() {
+        let mut blockchain = test_blockchain();
+        assert!(blockchain.add_transaction(test_transaction("AddressA", 0, "sigA1")).is_err());
+        assert!(blockchain.pending_transactions.is_empty());
+
+        mine(&mut blockchain);
+        assert!(blockchain.get_latest_block().transactions.is_empty());
+    }
+
+    #[test]
+    fn save_to_unwritable_path_returns_error() {
+        let path = temp_path("missing-dir").join("blockchain.json");
+        let err = save_blockchain_to(&path, &test_blockchain()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn pending_transactions_survive_save_and_load() {
+        let mut blockchain = test_blockchain();
+        let mined = test_transaction("AddressM", 1, "sigM1");
+        blockchain.add_transaction(mined.clone()).unwrap();
+        mine(&mut blockchain);
+
+        // Two pending transactions whose raw fields concatenate to the same bytes
+        let colliding = |sender: &str, receiver: &str| Transaction {
+            sender: sender.to_string(),
+            receiver: receiver.to_string(),
+            amount: 10,
+            timestamp: 1_700_000_000,
+            signature: "shared".to_string(),
+        };
+        blockchain.add_transaction(colliding("AB", "C")).unwrap();
+        blockchain.add_transaction(colliding("A", "BC")).unwrap();
+        blockchain.add_transaction(test_transaction("AddressC", 5, "sigC1")).unwrap();
+        let expected = blockchain.pending_transactions.clone();
+        // A stale pool entry that has since been mined must not come back
+        blockchain.pending_transactions.push(mined);
+
+        let path = temp_path("pending");
+        save_blockchain_to(&path, &blockchain).unwrap();
+        let loaded = load_blockchain_from(&path);
+        fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+
+        assert_eq!(loaded.pending_transactions.len(), 3);
+        for (loaded_tx, expected_tx) in loaded.pending_transactions.iter().zip(&expected) {
+            assert_eq!(loaded_tx.calculate_hash(), expected_tx.calculate_hash());
+            assert_eq!(loaded_tx.sender, expected_tx.sender);
+            assert_eq!(loaded_tx.receiver, expected_tx.receiver);
+        }
+        assert!(loaded.is_chain_valid());
+    }
+
+    fn blockchain_with_timestamps(timestamps: &[u64]) -> Blockchain {
+        let mut blockchain = test_blockchain();
+        blockchain.chain.clear();
+        for (index, &timestamp) in timestamps.iter().enumerate() {
+            let mut block = Block::new(index as u64, "0".to_string(), vec![]);
+            block.timestamp = timestamp;
+            blockchain.chain.push(block);
+        }
+        blockchain
+    }
+
+    #[test]
+    fn block_time_stats_odd_and_even_windows() {
+        // Intervals: 30, 10, 20, 70
+        let blockchain = blockchain_with_timestamps(&[100, 130, 140, 160, 230]);
+
+        let odd = blockchain.block_time_stats(3); // 10, 20, 70
+        assert_eq!(odd.avg, 100.0 / 3.0);
+        assert_eq!(odd.median, 20.0);
+        assert_eq!((odd.min, odd.max), (10, 70));
+
+        let even = blockchain.block_time_stats(4); // 30, 10, 20, 70
+        assert_eq!(even.avg, 32.5);
+        assert_eq!(even.median, 25.0);
+        assert_eq!((even.min, even.max), (10, 70));
+    }
+
+    #[test]
+    fn block_time_stats_zero_and_oversized_windows() {
+        let blockchain = blockchain_with_timestamps(&[100, 130, 140, 160, 230]);
+
+        let empty = blockchain.block_time_stats(0);
+        assert_eq!((empty.avg, empty.median, empty.min, empty.max), (0.0, 0.0, 0, 0));
+
+        for window in [5, 1000, usize::MAX] {
+            let stats = blockchain.block_time_stats(window);
+            assert_eq!(stats.avg, 32.5);
+            assert_eq!(stats.median, 25.0);
+            assert_eq!((stats.min, stats.max), (10, 70));
+        }
+    }
+
+    #[test]
+    fn add_transaction_returns_hash_or_specific_error() {
+        let mut blockchain = test_blockchain();
+
+        let tx = test_transaction("AddressA", 10, "sigA1");
+        let expected_hash = tx.calculate_hash();
+        assert_eq!(blockchain.add_transaction(tx), Ok(expected_hash));
+
+        assert_eq!(
+            blockchain.add_transaction(test_transaction("AddressA", 0, "sigA2")),
+            Err(TransactionError::ZeroAmount)
+        );
+    }
+
+    #[test]
+    fn get_transaction_pending_confirmed_and_unknown() {
+        let mut blockchain = test_blockchain();
+        let tx_hash = blockchain.add_transaction(test_transaction("AddressA", 10, "sigA1")).unwrap();
+
+        let pending = blockchain.get_transaction(&tx_hash).unwrap();
+        assert_eq!(pending.status, TransactionStatus::Pending);
+        assert_eq!(pending.confirmations, 0);
+        assert_eq!(pending.block_index, None);
+
+        mine(&mut blockchain);
+        let confirmed = blockchain.get_transaction(&tx_hash).unwrap();
+        assert_eq!(confirmed.status, TransactionStatus::Confirmed);
+        assert_eq!(confirmed.block_index, Some(1));
+        assert_eq!(confirmed.block_hash.as_deref(), Some(blockchain.chain[1].hash.as_str()));
+        assert_eq!(confirmed.confirmations, 1);
+
+        mine(&mut blockchain);
+        mine(&mut blockchain);
+        assert_eq!(blockchain.get_transaction(&tx_hash).unwrap().confirmations, 3);
+
+        assert!(blockchain.get_transaction("not-a-real-hash").is_none());
+    }
+
+    #[test]
+    fn duplicate_transaction_hash_is_rejected() {
+        let mut blockchain = test_blockchain();
+        let tx = test_transaction("AddressA", 10, "sigA1");
+        let tx_hash = blockchain.add_transaction(tx.clone()).unwrap();
+
+        let duplicate = Err(TransactionError::DuplicateTransaction { tx_hash: tx_hash.clone() });
+        assert_eq!(blockchain.add_transaction(tx.clone()), duplicate); // Still pending
+        mine(&mut blockchain);
+        assert_eq!(blockchain.add_transaction(tx), duplicate); // Already mined
+        assert_eq!(blockchain.pending_transactions.len(), 0);
+    }
+
+    #[test]
+    fn tx_index_keeps_first_location_of_a_repeated_hash() {
+        let mut blockchain = test_blockchain();
+        let tx = test_transaction("AddressA", 10, "sigA1");
+        let tx_hash = blockchain.add_transaction(tx.clone()).unwrap();
+        mine(&mut blockchain);
+
+        // Bypass add_transaction to get the same transaction into a later block
+        blockchain.pending_transactions.push(tx);
+        mine(&mut blockchain);
+        assert_eq!(blockchain.chain[2].transactions.len(), 1);
+
+        let lookup = blockchain.get_transaction(&tx_hash).unwrap();
+        assert_eq!(lookup.block_index, Some(1));
+        assert_eq!(lookup.confirmations, 2);
+
+        blockchain.rebuild_tx_index();
+        assert_eq!(blockchain.get_transaction(&tx_hash).unwrap().block_index, Some(1));
+    }
+
+    #[test]
+    fn tx_index_rebuilt_after_serde_round_trip() {
+        let mut blockchain = test_blockchain();
+        let mined_hash = blockchain.add_transaction(test_transaction("AddressA", 10, "sigA1")).unwrap();
+        mine(&mut blockchain);
+        mine(&mut blockchain);
+        let pending_hash = blockchain.add_transaction(test_transaction("AddressC", 5, "sigC1")).unwrap();
+
+        let json = serde_json::to_string(&blockchain).unwrap();
+        let mut loaded: Blockchain = serde_json::from_str(&json).unwrap();
+        assert!(loaded.tx_index.is_empty()); // Not persisted
+        loaded.rebuild_tx_index();
+        assert_eq!(loaded.tx_index, blockchain.tx_index);
+
+        let original = blockchain.get_transaction(&mined_hash).unwrap();
+        let reloaded = loaded.get_transaction(&mined_hash).unwrap();
+        assert_eq!(reloaded.status, original.status);
+        assert_eq!(reloaded.block_index, original.block_index);
+        assert_eq!(reloaded.block_hash, original.block_hash);
+        assert_eq!(reloaded.confirmations, original.confirmations);
+        let pending = loaded.get_transaction(&pending_hash).unwrap();
+        assert_eq!(pending.status, TransactionStatus::Pending);
+    }
+
+    #[test]
+    fn transaction_hash_separates_field_boundaries() {
+        let tx = |sender: &str, receiver: &str, signature: &str| Transaction {
+            sender: sender.to_string(),
+            receiver: receiver.to_string(),
+            amount: 10,
+            timestamp: 1_700_000_000,
+            signature: signature.to_string(),
+        };
+        assert_ne!(tx("AB", "C", "sig").calculate_hash(), tx("A", "BC", "sig").calculate_hash());
+        assert_ne!(tx("A", "BC", "sig").calculate_hash(), tx("A", "B", "Csig").calculate_hash());
+        assert_eq!(tx("AB", "C", "sig").calculate_hash(), tx("AB", "C", "sig").calculate_hash());
+
+        // amount and timestamp are fixed-width, so digits can't slide between them
+        let mut a = tx("A", "B", "sig");
+        let mut b = tx("A", "B", "sig");
+        (a.amount, a.timestamp) = (1, 23);
+        (b.amount, b.timestamp) = (12, 3);
+        assert_ne!(a.calculate_hash(), b.calculate_hash());
+    }
 }